@@ -0,0 +1,375 @@
+//! A write-through caching layer in front of [RormStore].
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+
+use async_trait::async_trait;
+use dashmap::DashMap;
+use futures::try_join;
+use rorm::internal::field::Field;
+use rorm::Model;
+use rorm::Patch;
+use thiserror::Error;
+use tower_sessions::cookie::time::OffsetDateTime;
+use tower_sessions::session::Id;
+use tower_sessions::session::Record;
+use tower_sessions::session_store::Error;
+use tower_sessions::session_store::Result;
+use tower_sessions::ExpiredDeletion;
+use tower_sessions::SessionStore;
+use tracing::instrument;
+
+use crate::RormStore;
+use crate::SessionModel;
+
+/// A concurrent in-process cache that can front a [CachingRormStore].
+///
+/// Implemented for [DashMap] out of the box; swap in another implementation
+/// (e.g. a `moka` cache) to get different eviction behavior.
+pub trait SessionCache: Send + Sync + 'static {
+    /// Error produced by the cache backend.
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Look up the cached record for `id`.
+    ///
+    /// Implementations should ignore (and ideally evict) entries whose
+    /// `expiry_date` has already passed.
+    fn get(&self, id: &Id) -> std::result::Result<Option<Record>, Self::Error>;
+
+    /// Insert or replace the cached record for `id`.
+    fn insert(&self, id: Id, record: Record) -> std::result::Result<(), Self::Error>;
+
+    /// Remove any cached record for `id`.
+    fn remove(&self, id: &Id) -> std::result::Result<(), Self::Error>;
+}
+
+impl SessionCache for DashMap<Id, Record> {
+    type Error = std::convert::Infallible;
+
+    fn get(&self, id: &Id) -> std::result::Result<Option<Record>, Self::Error> {
+        Ok(self.get(id).and_then(|entry| {
+            let record = entry.value().clone();
+            (record.expiry_date > OffsetDateTime::now_utc()).then_some(record)
+        }))
+    }
+
+    fn insert(&self, id: Id, record: Record) -> std::result::Result<(), Self::Error> {
+        DashMap::insert(self, id, record);
+        Ok(())
+    }
+
+    fn remove(&self, id: &Id) -> std::result::Result<(), Self::Error> {
+        DashMap::remove(self, id);
+        Ok(())
+    }
+}
+
+/// A write-through cache in front of [RormStore].
+///
+/// `load` is served from `Cache` whenever possible and only falls through to
+/// the wrapped [RormStore] on a miss, populating the cache afterward. `save`
+/// and `delete` fan out to the cache and the database store concurrently, so
+/// the cache write costs nothing in latency over talking to the database
+/// alone.
+///
+/// Caution: this is at odds with [RormStore::with_idle_ttl]. Sliding
+/// expiration only advances `expires_at` inside `RormStore::load`, which a
+/// cache hit here never calls — so once a session is cached, its deadline
+/// stops sliding and it expires at the first cached `expiry_date` even
+/// under continuous activity. Don't enable idle TTL on a store wrapped by
+/// `CachingRormStore` without also periodically re-touching the underlying
+/// store for cached sessions.
+pub struct CachingRormStore<Cache, S> {
+    store: RormStore<S>,
+    cache: Cache,
+}
+
+impl<Cache, S> CachingRormStore<Cache, S> {
+    /// Wrap `store`, serving reads and writes through `cache` first.
+    pub fn new(store: RormStore<S>, cache: Cache) -> Self {
+        Self { store, cache }
+    }
+}
+
+impl<Cache, S> Debug for CachingRormStore<Cache, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "")
+    }
+}
+
+impl<Cache: Clone, S> Clone for CachingRormStore<Cache, S> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            cache: self.cache.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl<Cache, S> ExpiredDeletion for CachingRormStore<Cache, S>
+where
+    Cache: SessionCache,
+    S: Model + SessionModel + Debug,
+    <S as Model>::Primary: Field<Type = String>,
+    <S as Patch>::Decoder: Send + Sync + 'static,
+{
+    #[instrument(level = "trace")]
+    async fn delete_expired(&self) -> Result<()> {
+        self.store.delete_expired().await
+    }
+}
+
+#[async_trait]
+impl<Cache, S> SessionStore for CachingRormStore<Cache, S>
+where
+    Cache: SessionCache,
+    S: Model + Send + Sync + SessionModel,
+    <S as Model>::Primary: Field<Type = String>,
+    <S as Patch>::Decoder: Send + Sync + 'static,
+{
+    #[instrument(level = "trace")]
+    async fn create(&self, session_record: &mut Record) -> Result<()> {
+        self.store
+            .create(session_record)
+            .await
+            .map_err(CachingRormStoreError::<Cache::Error>::Store)?;
+
+        self.cache
+            .insert(session_record.id, session_record.clone())
+            .map_err(CachingRormStoreError::<Cache::Error>::Cache)?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace")]
+    async fn save(&self, session_record: &Record) -> Result<()> {
+        let previous = self
+            .cache
+            .get(&session_record.id)
+            .map_err(CachingRormStoreError::<Cache::Error>::Cache)?;
+
+        let store_fut = async {
+            self.store
+                .save(session_record)
+                .await
+                .map_err(CachingRormStoreError::<Cache::Error>::Store)
+        };
+        let cache_fut = async {
+            self.cache
+                .insert(session_record.id, session_record.clone())
+                .map_err(CachingRormStoreError::<Cache::Error>::Cache)
+        };
+
+        if let Err(error) = try_join!(store_fut, cache_fut) {
+            reconcile_cache_on_write_failure(&self.cache, session_record.id, previous, &error);
+            return Err(Error::from(error));
+        }
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace")]
+    async fn load(&self, session_id: &Id) -> Result<Option<Record>> {
+        if let Some(record) = self
+            .cache
+            .get(session_id)
+            .map_err(CachingRormStoreError::<Cache::Error>::Cache)?
+        {
+            return Ok(Some(record));
+        }
+
+        let record = self
+            .store
+            .load(session_id)
+            .await
+            .map_err(CachingRormStoreError::<Cache::Error>::Store)?;
+
+        if let Some(record) = &record {
+            self.cache
+                .insert(record.id, record.clone())
+                .map_err(CachingRormStoreError::<Cache::Error>::Cache)?;
+        }
+
+        Ok(record)
+    }
+
+    #[instrument(level = "trace")]
+    async fn delete(&self, session_id: &Id) -> Result<()> {
+        let previous = self
+            .cache
+            .get(session_id)
+            .map_err(CachingRormStoreError::<Cache::Error>::Cache)?;
+
+        let store_fut = async {
+            self.store
+                .delete(session_id)
+                .await
+                .map_err(CachingRormStoreError::<Cache::Error>::Store)
+        };
+        let cache_fut = async {
+            self.cache
+                .remove(session_id)
+                .map_err(CachingRormStoreError::<Cache::Error>::Cache)
+        };
+
+        if let Err(error) = try_join!(store_fut, cache_fut) {
+            reconcile_cache_on_write_failure(&self.cache, *session_id, previous, &error);
+            return Err(Error::from(error));
+        }
+
+        Ok(())
+    }
+}
+
+/// Reconcile the cache after a concurrent cache/store write in `save` or
+/// `delete` failed, so a subsequent [SessionStore::load] can't observe data
+/// that diverges from what's now authoritative.
+///
+/// `try_join!` returns on the first error and drops the other write
+/// unfinished. A `Store` failure means the database rejected the write while
+/// the cache may already hold the value that `save`/`delete` attempted, so
+/// the entry is rolled back to `previous` (or evicted, if there was no
+/// previous entry). A `Cache` failure leaves both the cache and the store
+/// write in an unknown state (the store write may have completed or been
+/// dropped), so the entry is evicted rather than risk serving the stale
+/// pre-write value; the next `load` will fall through to the store.
+fn reconcile_cache_on_write_failure<Cache: SessionCache>(
+    cache: &Cache,
+    id: Id,
+    previous: Option<Record>,
+    error: &CachingRormStoreError<Cache::Error>,
+) {
+    match error {
+        CachingRormStoreError::Store(_) => match previous {
+            Some(record) => drop(cache.insert(id, record)),
+            None => drop(cache.remove(&id)),
+        },
+        CachingRormStoreError::Cache(_) => drop(cache.remove(&id)),
+    }
+}
+
+/// Error type that is used in [CachingRormStore]'s [SessionStore] implementation.
+#[derive(Debug, Error)]
+#[allow(missing_docs)]
+pub enum CachingRormStoreError<CacheError> {
+    #[error("Cache error: {0}")]
+    Cache(CacheError),
+    #[error("Store error: {0}")]
+    Store(Error),
+}
+
+impl<CacheError: std::error::Error> From<CachingRormStoreError<CacheError>> for Error {
+    fn from(value: CachingRormStoreError<CacheError>) -> Self {
+        Self::Backend(value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    use tower_sessions::cookie::time::Duration;
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct MockCacheError;
+
+    impl std::fmt::Display for MockCacheError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "mock cache failure")
+        }
+    }
+
+    impl std::error::Error for MockCacheError {}
+
+    /// An in-memory [SessionCache] whose `insert` can be forced to fail, to
+    /// exercise the recovery path without needing a real [RormStore].
+    struct MockCache {
+        entries: Mutex<HashMap<Id, Record>>,
+        fail_inserts: bool,
+    }
+
+    impl MockCache {
+        fn new(fail_inserts: bool) -> Self {
+            Self {
+                entries: Mutex::new(HashMap::new()),
+                fail_inserts,
+            }
+        }
+    }
+
+    impl SessionCache for MockCache {
+        type Error = MockCacheError;
+
+        fn get(&self, id: &Id) -> std::result::Result<Option<Record>, Self::Error> {
+            Ok(self.entries.lock().unwrap().get(id).cloned())
+        }
+
+        fn insert(&self, id: Id, record: Record) -> std::result::Result<(), Self::Error> {
+            if self.fail_inserts {
+                return Err(MockCacheError);
+            }
+            self.entries.lock().unwrap().insert(id, record);
+            Ok(())
+        }
+
+        fn remove(&self, id: &Id) -> std::result::Result<(), Self::Error> {
+            self.entries.lock().unwrap().remove(id);
+            Ok(())
+        }
+    }
+
+    fn sample_record(id: Id) -> Record {
+        Record {
+            id,
+            data: HashMap::new(),
+            expiry_date: OffsetDateTime::now_utc() + Duration::minutes(5),
+        }
+    }
+
+    #[test]
+    fn store_failure_restores_previous_value() {
+        let cache = MockCache::new(false);
+        let id = Id::default();
+        let stale = sample_record(id);
+        cache.insert(id, stale.clone()).unwrap();
+
+        let error = CachingRormStoreError::<MockCacheError>::Store(Error::Backend("boom".into()));
+        reconcile_cache_on_write_failure(&cache, id, Some(stale.clone()), &error);
+
+        let restored = cache.get(&id).unwrap();
+        assert!(restored.is_some());
+        assert_eq!(restored.unwrap().expiry_date, stale.expiry_date);
+    }
+
+    #[test]
+    fn store_failure_with_no_previous_evicts() {
+        let cache = MockCache::new(false);
+        let id = Id::default();
+        cache.insert(id, sample_record(id)).unwrap();
+
+        let error = CachingRormStoreError::<MockCacheError>::Store(Error::Backend("boom".into()));
+        reconcile_cache_on_write_failure(&cache, id, None, &error);
+
+        assert!(cache.get(&id).unwrap().is_none());
+    }
+
+    #[test]
+    fn cache_failure_evicts_rather_than_restoring_stale_value() {
+        // `fail_inserts` simulates the cache write failing while it still
+        // holds the stale pre-write value; the cache must not be left
+        // holding that value.
+        let cache = MockCache::new(true);
+        let id = Id::default();
+        let stale = sample_record(id);
+        cache.entries.lock().unwrap().insert(id, stale.clone());
+
+        let error = CachingRormStoreError::<MockCacheError>::Cache(MockCacheError);
+        reconcile_cache_on_write_failure(&cache, id, Some(stale), &error);
+
+        assert!(cache.get(&id).unwrap().is_none());
+    }
+}
@@ -0,0 +1,225 @@
+//! A transparent encryption layer in front of [RormStore].
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::Aead;
+use chacha20poly1305::aead::OsRng;
+use chacha20poly1305::AeadCore;
+use chacha20poly1305::ChaCha20Poly1305;
+use chacha20poly1305::Key;
+use chacha20poly1305::KeyInit;
+use rorm::internal::field::Field;
+use rorm::Model;
+use rorm::Patch;
+pub use serde_json::Value;
+use tower_sessions::session::Id;
+use tower_sessions::session::Record;
+use tower_sessions::session_store::Result;
+use tower_sessions::ExpiredDeletion;
+use tower_sessions::SessionStore;
+use tracing::instrument;
+
+use crate::RormStore;
+use crate::RormStoreError;
+use crate::SessionModel;
+
+const NONCE_LEN: usize = 12;
+const RESERVED_KEY: &str = "__enc";
+
+/// A layer in front of [RormStore] that transparently encrypts session data
+/// before it ever reaches the database and decrypts it again on load, so an
+/// operator with read access to a shared database never sees plaintext
+/// session contents.
+///
+/// The whole `data` map is serialized to JSON, encrypted with
+/// ChaCha20-Poly1305 using a fresh random nonce per save, and stored
+/// base64-encoded behind a single reserved key inside the same
+/// `Json<HashMap<String, Value>>` column [RormStore] already writes, so no
+/// schema change is required.
+pub struct EncryptedRormStore<S> {
+    store: RormStore<S>,
+    cipher: ChaCha20Poly1305,
+}
+
+impl<S> EncryptedRormStore<S> {
+    /// Wrap `store`, encrypting all session data with the given 32-byte key.
+    pub fn new(store: RormStore<S>, key: &[u8; 32]) -> Self {
+        Self {
+            store,
+            cipher: ChaCha20Poly1305::new(Key::from_slice(key)),
+        }
+    }
+
+    fn encrypt(
+        &self,
+        data: &HashMap<String, Value>,
+    ) -> std::result::Result<HashMap<String, Value>, RormStoreError> {
+        encrypt_data(&self.cipher, data)
+    }
+
+    fn decrypt(
+        &self,
+        data: HashMap<String, Value>,
+    ) -> std::result::Result<HashMap<String, Value>, RormStoreError> {
+        decrypt_data(&self.cipher, data)
+    }
+}
+
+fn encrypt_data(
+    cipher: &ChaCha20Poly1305,
+    data: &HashMap<String, Value>,
+) -> std::result::Result<HashMap<String, Value>, RormStoreError> {
+    let plaintext = serde_json::to_vec(data)?;
+
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext.as_slice())
+        .map_err(|_| RormStoreError::Encryption)?;
+
+    let mut payload = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+    payload.extend_from_slice(&nonce);
+    payload.extend_from_slice(&ciphertext);
+
+    let mut encrypted = HashMap::with_capacity(1);
+    encrypted.insert(RESERVED_KEY.to_owned(), Value::String(BASE64.encode(payload)));
+    Ok(encrypted)
+}
+
+fn decrypt_data(
+    cipher: &ChaCha20Poly1305,
+    data: HashMap<String, Value>,
+) -> std::result::Result<HashMap<String, Value>, RormStoreError> {
+    let encoded = data
+        .get(RESERVED_KEY)
+        .and_then(Value::as_str)
+        .ok_or(RormStoreError::Decryption)?;
+
+    let payload = BASE64
+        .decode(encoded)
+        .map_err(|_| RormStoreError::Decryption)?;
+    if payload.len() < NONCE_LEN {
+        return Err(RormStoreError::Decryption);
+    }
+    let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+
+    let plaintext = cipher
+        .decrypt(nonce.into(), ciphertext)
+        .map_err(|_| RormStoreError::Decryption)?;
+
+    Ok(serde_json::from_slice(&plaintext)?)
+}
+
+impl<S> Debug for EncryptedRormStore<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "")
+    }
+}
+
+impl<S> Clone for EncryptedRormStore<S> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            cipher: self.cipher.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl<S> ExpiredDeletion for EncryptedRormStore<S>
+where
+    S: Model + SessionModel + Debug,
+    <S as Model>::Primary: Field<Type = String>,
+    <S as Patch>::Decoder: Send + Sync + 'static,
+{
+    #[instrument(level = "trace")]
+    async fn delete_expired(&self) -> Result<()> {
+        self.store.delete_expired().await
+    }
+}
+
+#[async_trait]
+impl<S> SessionStore for EncryptedRormStore<S>
+where
+    S: Model + Send + Sync + SessionModel,
+    <S as Model>::Primary: Field<Type = String>,
+    <S as Patch>::Decoder: Send + Sync + 'static,
+{
+    #[instrument(level = "trace")]
+    async fn create(&self, session_record: &mut Record) -> Result<()> {
+        let mut encrypted_record = session_record.clone();
+        encrypted_record.data = self.encrypt(&session_record.data)?;
+
+        self.store.create(&mut encrypted_record).await?;
+        session_record.id = encrypted_record.id;
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace")]
+    async fn save(&self, session_record: &Record) -> Result<()> {
+        let mut encrypted_record = session_record.clone();
+        encrypted_record.data = self.encrypt(&session_record.data)?;
+
+        self.store.save(&encrypted_record).await
+    }
+
+    #[instrument(level = "trace")]
+    async fn load(&self, session_id: &Id) -> Result<Option<Record>> {
+        let Some(mut record) = self.store.load(session_id).await? else {
+            return Ok(None);
+        };
+
+        record.data = self.decrypt(record.data)?;
+
+        Ok(Some(record))
+    }
+
+    #[instrument(level = "trace")]
+    async fn delete(&self, session_id: &Id) -> Result<()> {
+        self.store.delete(session_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_decrypt_round_trip() {
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&[7u8; 32]));
+
+        let mut data = HashMap::new();
+        data.insert("user_id".to_owned(), Value::from(42));
+
+        let encrypted = encrypt_data(&cipher, &data).unwrap();
+        assert!(encrypted.contains_key(RESERVED_KEY));
+
+        let decrypted = decrypt_data(&cipher, encrypted).unwrap();
+        assert_eq!(decrypted, data);
+    }
+
+    #[test]
+    fn decrypt_rejects_tampered_ciphertext() {
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&[7u8; 32]));
+
+        let mut data = HashMap::new();
+        data.insert("user_id".to_owned(), Value::from(42));
+
+        let mut encrypted = encrypt_data(&cipher, &data).unwrap();
+        let mut payload = BASE64
+            .decode(encrypted[RESERVED_KEY].as_str().unwrap())
+            .unwrap();
+        payload[NONCE_LEN] ^= 1;
+        encrypted.insert(RESERVED_KEY.to_owned(), Value::String(BASE64.encode(payload)));
+
+        assert!(matches!(
+            decrypt_data(&cipher, encrypted),
+            Err(RormStoreError::Decryption)
+        ));
+    }
+}
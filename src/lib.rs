@@ -9,6 +9,14 @@
 
 #![warn(missing_docs)]
 
+mod cache;
+mod encrypted;
+
+pub use cache::CachingRormStore;
+pub use cache::CachingRormStoreError;
+pub use cache::SessionCache;
+pub use encrypted::EncryptedRormStore;
+
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::fmt::Formatter;
@@ -30,6 +38,8 @@ use rorm::Model;
 use rorm::Patch;
 pub use serde_json::Value;
 use thiserror::Error;
+use tokio::task::JoinHandle;
+use tower_sessions::cookie::time::Duration;
 use tower_sessions::cookie::time::OffsetDateTime;
 use tower_sessions::session::Id;
 use tower_sessions::session::Record;
@@ -38,6 +48,7 @@ use tower_sessions::session_store::Result;
 use tower_sessions::ExpiredDeletion;
 use tower_sessions::SessionStore;
 use tracing::debug;
+use tracing::error;
 use tracing::instrument;
 
 /// Implement this trait on a [Model] that should be used
@@ -77,6 +88,7 @@ where
 /// The session store for rorm
 pub struct RormStore<S> {
     db: Database,
+    idle_ttl: Option<Duration>,
     marker: PhantomData<S>,
 }
 
@@ -85,9 +97,28 @@ impl<S> RormStore<S> {
     pub fn new(db: Database) -> Self {
         Self {
             db,
+            idle_ttl: None,
             marker: PhantomData,
         }
     }
+
+    /// Enable sliding expiration: every successful [SessionStore::load] pushes
+    /// the session's `expires_at` forward to `now + idle_ttl`, so actively
+    /// used sessions are kept alive while abandoned ones still expire.
+    ///
+    /// Unset by default, which preserves the fixed-deadline behavior.
+    ///
+    /// Caution: this only re-touches `expires_at` on calls that actually
+    /// reach this store's `load`. If this `RormStore` is wrapped in
+    /// [CachingRormStore], cache hits never call back into `load`, so a
+    /// session that stays cached degrades to expiring once at its first
+    /// cached deadline instead of sliding — the opposite of what this mode
+    /// is for. Don't combine the two without also refreshing `expires_at`
+    /// from the cache-hit path.
+    pub fn with_idle_ttl(mut self, idle_ttl: Duration) -> Self {
+        self.idle_ttl = Some(idle_ttl);
+        self
+    }
 }
 
 impl<S> Debug for RormStore<S> {
@@ -100,26 +131,77 @@ impl<S> Clone for RormStore<S> {
     fn clone(&self) -> Self {
         Self {
             db: self.db.clone(),
+            idle_ttl: self.idle_ttl,
             marker: PhantomData,
         }
     }
 }
 
-#[async_trait]
-impl<S> ExpiredDeletion for RormStore<S>
+impl<S> RormStore<S>
 where
-    S: Model + SessionModel + Debug,
+    S: Model + SessionModel + Debug + Send + Sync + 'static,
     <S as Model>::Primary: Field<Type = String>,
     <S as Patch>::Decoder: Send + Sync + 'static,
 {
+    /// Delete every session unconditionally, returning the number of rows
+    /// removed.
+    ///
+    /// Useful for test teardown or to invalidate every outstanding session
+    /// after a key rotation.
     #[instrument(level = "trace")]
-    async fn delete_expired(&self) -> Result<()> {
+    pub async fn clear_store(&self) -> Result<u64> {
         let db = &self.db;
 
-        delete!(db, S)
+        let deleted = delete!(db, S).await.map_err(RormStoreError::from)?;
+
+        Ok(deleted)
+    }
+
+    /// Delete all expired sessions, returning the number of rows removed.
+    ///
+    /// This is what backs [ExpiredDeletion::delete_expired]; use this
+    /// version directly when the caller wants to observe the count, e.g. to
+    /// emit metrics.
+    #[instrument(level = "trace")]
+    pub async fn delete_expired_count(&self) -> Result<u64> {
+        let db = &self.db;
+
+        let deleted = delete!(db, S)
             .condition(S::get_expires_at_field().less_than(OffsetDateTime::now_utc()))
             .await
             .map_err(RormStoreError::from)?;
+        debug!(deleted, "Deleted expired sessions");
+
+        Ok(deleted)
+    }
+
+    /// Spawn a background task that calls [RormStore::delete_expired_count]
+    /// on a fixed `period`, logging (rather than aborting on) errors.
+    pub fn continuously_delete_expired(self, period: std::time::Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+
+            loop {
+                interval.tick().await;
+
+                if let Err(error) = self.delete_expired_count().await {
+                    error!(%error, "Failed to delete expired sessions");
+                }
+            }
+        })
+    }
+}
+
+#[async_trait]
+impl<S> ExpiredDeletion for RormStore<S>
+where
+    S: Model + SessionModel + Debug,
+    <S as Model>::Primary: Field<Type = String>,
+    <S as Patch>::Decoder: Send + Sync + 'static,
+{
+    #[instrument(level = "trace")]
+    async fn delete_expired(&self) -> Result<()> {
+        self.delete_expired_count().await?;
 
         Ok(())
     }
@@ -135,38 +217,30 @@ where
     #[instrument(level = "trace")]
     async fn create(&self, session_record: &mut Record) -> Result<()> {
         debug!("Creating new session");
-        let mut tx = self
-            .db
-            .start_transaction()
-            .await
-            .map_err(RormStoreError::from)?;
-        loop {
-            let existing = query!(&mut tx, S)
-                .condition(S::get_primary_field().equals(session_record.id.to_string()))
-                .optional()
+        let db = &self.db;
+
+        const MAX_ATTEMPTS: u8 = 5;
+
+        for _ in 0..MAX_ATTEMPTS {
+            let inserted = insert!(db, S)
+                .on_conflict(S::get_primary_field())
+                .do_nothing()
+                .single(&S::get_insert_patch(
+                    session_record.id.to_string(),
+                    session_record.expiry_date,
+                    Json(session_record.data.clone()),
+                ))
                 .await
                 .map_err(RormStoreError::from)?;
 
-            if existing.is_none() {
-                insert!(&mut tx, S)
-                    .return_nothing()
-                    .single(&S::get_insert_patch(
-                        session_record.id.to_string(),
-                        session_record.expiry_date,
-                        Json(session_record.data.clone()),
-                    ))
-                    .await
-                    .map_err(RormStoreError::from)?;
-
-                break;
+            if inserted > 0 {
+                return Ok(());
             }
 
             session_record.id = Id::default();
         }
 
-        tx.commit().await.map_err(RormStoreError::from)?;
-
-        Ok(())
+        Err(RormStoreError::IdCollision.into())
     }
 
     #[instrument(level = "trace")]
@@ -176,40 +250,22 @@ where
             data,
             expiry_date,
         } = session_record;
+        let db = &self.db;
 
-        let mut tx = self
-            .db
-            .start_transaction()
-            .await
-            .map_err(RormStoreError::from)?;
-
-        let existing_session = query!(&mut tx, S)
-            .condition(S::get_primary_field().equals(id.to_string()))
-            .optional()
+        insert!(db, S)
+            .return_nothing()
+            .on_conflict(S::get_primary_field())
+            .do_update()
+            .set(S::get_expires_at_field(), *expiry_date)
+            .set(S::get_data_field(), Json(data.clone()))
+            .single(&S::get_insert_patch(
+                id.to_string(),
+                *expiry_date,
+                Json(data.clone()),
+            ))
             .await
             .map_err(RormStoreError::from)?;
 
-        if existing_session.is_some() {
-            update!(&mut tx, S)
-                .condition(S::get_primary_field().equals(id.to_string()))
-                .set(S::get_expires_at_field(), *expiry_date)
-                .set(S::get_data_field(), Json(data.clone()))
-                .exec()
-                .await
-                .map_err(RormStoreError::from)?;
-        } else {
-            insert!(&mut tx, S)
-                .single(&S::get_insert_patch(
-                    id.to_string(),
-                    *expiry_date,
-                    Json(data.clone()),
-                ))
-                .await
-                .map_err(RormStoreError::from)?;
-        }
-
-        tx.commit().await.map_err(RormStoreError::from)?;
-
         Ok(())
     }
 
@@ -232,10 +288,25 @@ where
             Some(session) => {
                 let (id, expiry, data) = session.get_session_data();
 
+                let expiry_date = if let Some(idle_ttl) = self.idle_ttl {
+                    let expiry_date = OffsetDateTime::now_utc() + idle_ttl;
+
+                    update!(db, S)
+                        .condition(S::get_primary_field().equals(id.clone()))
+                        .set(S::get_expires_at_field(), expiry_date)
+                        .exec()
+                        .await
+                        .map_err(RormStoreError::from)?;
+
+                    expiry_date
+                } else {
+                    expiry
+                };
+
                 Some(Record {
                     id: Id::from_str(id.as_str()).map_err(RormStoreError::from)?,
                     data: data.into_inner(),
-                    expiry_date: expiry,
+                    expiry_date,
                 })
             }
         })
@@ -262,6 +333,14 @@ pub enum RormStoreError {
     Database(#[from] rorm::Error),
     #[error("Decoding of id failed: {0}")]
     DecodingFailed(#[from] base64::DecodeSliceError),
+    #[error("Failed to encrypt session data")]
+    Encryption,
+    #[error("Failed to decrypt session data")]
+    Decryption,
+    #[error("Failed to (de)serialize session data: {0}")]
+    Encoding(#[from] serde_json::Error),
+    #[error("Exhausted retries generating a unique session id")]
+    IdCollision,
 }
 
 impl From<RormStoreError> for Error {